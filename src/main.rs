@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::str::FromStr;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Clone, Debug, Parser)]
 struct Args {
@@ -9,7 +9,31 @@ struct Args {
     wordlist: std::path::PathBuf,
 
     #[arg(short, long, default_value_t = 5)]
-    length: usize
+    length: usize,
+
+    #[arg(long, value_enum, default_value_t = Strategy::Frequency)]
+    strategy: Strategy,
+
+    #[arg(long, default_value_t = false)]
+    benchmark: bool,
+
+    #[arg(long, default_value_t = 6)]
+    max_guesses: usize,
+
+    #[arg(long, default_value_t = false)]
+    play: bool,
+
+    #[arg(long)]
+    frequencies: Option<std::path::PathBuf>,
+
+    #[arg(long, default_value_t = 0.0)]
+    common_bias: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Strategy {
+    Frequency,
+    Entropy,
 }
 
 #[derive(Clone, Debug)]
@@ -20,6 +44,7 @@ enum Command {
     PruneAt(char, usize),
     Require(String),
     RequireAt(char, usize),
+    Undo(usize),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -35,9 +60,17 @@ impl FromStr for Command {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use CommandError::*;
 
-        let (cmd, args) = s.trim().split_once(' ').ok_or(ImproperArgumentCount)?;
+        let trimmed = s.trim();
+        if trimmed == "undo" {
+            return Ok(Command::Undo(1));
+        }
+
+        let (cmd, args) = trimmed.split_once(' ').ok_or(ImproperArgumentCount)?;
         match cmd {
             "contains" => Ok(Command::Contains(args.to_string())),
+            "undo" => Ok(Command::Undo(
+                args.trim().parse().map_err(|_| MalformedArgument)?,
+            )),
             "guess" | "g" => {
                 let to_vec =
                     |s: &str| Vec::from_iter(s.chars().map(|c| c.to_digit(10).unwrap() as usize));
@@ -45,11 +78,20 @@ impl FromStr for Command {
                 let (word, indices) = args.split_once(' ').ok_or(ImproperArgumentCount)?;
                 let (correct, incorrect) =
                     indices.trim().split_once(',').ok_or(MalformedArgument)?;
-                Ok(Command::Guess(
-                    word.to_string(),
-                    to_vec(correct),
-                    to_vec(incorrect),
-                ))
+                let correct = to_vec(correct);
+                let incorrect = to_vec(incorrect);
+
+                let len = word.trim().chars().count();
+                if correct.iter().chain(incorrect.iter()).any(|&i| i >= len) {
+                    return Err(MalformedArgument);
+                }
+
+                let mut seen = HashSet::new();
+                if !correct.iter().chain(incorrect.iter()).all(|&i| seen.insert(i)) {
+                    return Err(MalformedArgument);
+                }
+
+                Ok(Command::Guess(word.to_string(), correct, incorrect))
             }
             "prune" | "p" => {
                 let mut tokens = args.split(" at ");
@@ -88,21 +130,45 @@ enum Tile {
     Unchecked(char),
 }
 
+type PatternCache = HashMap<(String, String), usize>;
+
 fn main() {
     let args = Args::parse();
-    
-    let mut words: HashSet<_> = std::fs::read_to_string(args.wordlist)
-        .expect("Could not open wordlist")
-        .lines()
-        .filter(|word| word.chars().count() == args.length)
-        .map(|word| word.trim().to_ascii_lowercase())
-        .collect();
+
+    let (all_words, mut weights) = load_wordlist(&args.wordlist, args.length);
+
+    if let Some(frequencies_path) = &args.frequencies {
+        load_frequencies(frequencies_path, &mut weights);
+    }
+
+    let normalized_weights = normalize_log_weights(&all_words, &weights);
+
+    let mut words: HashSet<String> = all_words.iter().cloned().collect();
 
     println!("Wordlist contains {} words", words.len());
 
+    if args.benchmark {
+        run_benchmark(
+            &all_words,
+            args.strategy,
+            args.length,
+            args.max_guesses,
+            args.common_bias,
+            &normalized_weights,
+        );
+        return;
+    }
+
+    if args.play {
+        run_play(&all_words, args.length, args.max_guesses);
+        return;
+    }
+
     let mut guesses: Vec<String> = Vec::new();
+    let mut history: Vec<Command> = Vec::new();
+    let mut pattern_cache: PatternCache = HashMap::new();
 
-    while words.len() > 1 {
+    while words.len() != 1 {
         println!("Guesses: {:?}", guesses);
 
         print!("$ ");
@@ -121,6 +187,119 @@ fn main() {
                 println!("{}", words.contains(&word));
                 continue;
             }
+            Command::Undo(n) => {
+                let new_len = history.len().saturating_sub(n);
+                history.truncate(new_len);
+            }
+            mutating => history.push(mutating),
+        }
+
+        let (new_words, new_guesses) = replay(&history, &all_words);
+        words = new_words;
+        guesses = new_guesses;
+
+        println!("{:?}", words);
+        println!("{}", words.len());
+
+        if words.is_empty() {
+            println!("No candidates left -- type `undo` to recover");
+            continue;
+        }
+
+        let recommendation = recommend(
+            &words,
+            &all_words,
+            args.strategy,
+            args.length,
+            args.common_bias,
+            &normalized_weights,
+            &mut pattern_cache,
+        );
+        match args.strategy {
+            Strategy::Frequency => {
+                let letter_counts = letter_counts(&words);
+                println!(
+                    "Recommended guess: {} ({})",
+                    recommendation,
+                    (score(&recommendation, &letter_counts) as f64) / (words.len() as f64)
+                );
+            }
+            Strategy::Entropy => {
+                println!(
+                    "Recommended guess: {} ({:.3} bits)",
+                    recommendation,
+                    entropy(&recommendation, &words, args.length, &mut pattern_cache)
+                );
+            }
+        }
+    }
+}
+
+fn load_wordlist(path: &std::path::Path, length: usize) -> (Vec<String>, HashMap<String, f64>) {
+    let mut weights = HashMap::new();
+    let words = std::fs::read_to_string(path)
+        .expect("Could not open wordlist")
+        .lines()
+        .filter_map(|line| {
+            let (word, frequency) = match line.split_once('\t') {
+                Some((word, frequency)) => (word, frequency.trim().parse::<f64>().ok()),
+                None => (line, None),
+            };
+            let word = word.trim().to_ascii_lowercase();
+            if word.chars().count() != length {
+                return None;
+            }
+            if let Some(frequency) = frequency {
+                weights.insert(word.clone(), frequency);
+            }
+            Some(word)
+        })
+        .collect();
+
+    (words, weights)
+}
+
+fn load_frequencies(path: &std::path::Path, weights: &mut HashMap<String, f64>) {
+    for line in std::fs::read_to_string(path)
+        .expect("Could not open frequencies file")
+        .lines()
+    {
+        if let Some((word, frequency)) = line.split_once('\t') {
+            if let Ok(frequency) = frequency.trim().parse::<f64>() {
+                weights.insert(word.trim().to_ascii_lowercase(), frequency);
+            }
+        }
+    }
+}
+
+fn normalize_log_weights(all_words: &[String], weights: &HashMap<String, f64>) -> HashMap<String, f64> {
+    let log_weights: Vec<(String, f64)> = all_words
+        .iter()
+        .map(|word| {
+            let weight = weights.get(word).copied().unwrap_or(1.0).max(f64::MIN_POSITIVE);
+            (word.clone(), weight.ln())
+        })
+        .collect();
+
+    let min = log_weights.iter().map(|(_, w)| *w).fold(f64::INFINITY, f64::min);
+    let max = log_weights.iter().map(|(_, w)| *w).fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    log_weights
+        .into_iter()
+        .map(|(word, log_weight)| {
+            let normalized = if range > 0.0 { (log_weight - min) / range } else { 0.0 };
+            (word, normalized)
+        })
+        .collect()
+}
+
+fn replay(history: &[Command], all_words: &[String]) -> (HashSet<String>, Vec<String>) {
+    let mut words: HashSet<String> = all_words.iter().cloned().collect();
+    let mut guesses: Vec<String> = Vec::new();
+
+    for command in history {
+        match command.clone() {
             Command::Prune(chars) => {
                 chars.chars().for_each(|ch| prune(&mut words, ch));
             }
@@ -185,21 +364,196 @@ fn main() {
                     }
                 }
             }
+            Command::Contains(_) | Command::Undo(_) => {
+                unreachable!("non-mutating commands are never pushed onto history")
+            }
         }
+    }
 
-        println!("{:?}", words);
-        println!("{}", words.len());
-        let letter_counts = letter_counts(&words);
-        let recommendation = words
-            .iter()
-            .max_by(|a, b| score(a, &letter_counts).cmp(&score(b, &letter_counts)))
-            .unwrap_or_else(|| panic!("Empty wordlist"));
-        println!(
-            "Recommended guess: {} ({})",
-            recommendation,
-            (score(recommendation, &letter_counts) as f64) / (words.len() as f64)
-        );
+    (words, guesses)
+}
+
+fn recommend(
+    words: &HashSet<String>,
+    all_words: &[String],
+    strategy: Strategy,
+    length: usize,
+    common_bias: f64,
+    normalized_weights: &HashMap<String, f64>,
+    pattern_cache: &mut PatternCache,
+) -> String {
+    match strategy {
+        Strategy::Frequency => {
+            let letter_counts = letter_counts(words);
+            words
+                .iter()
+                .max_by(|a, b| {
+                    blended_score(a, &letter_counts, normalized_weights, common_bias)
+                        .partial_cmp(&blended_score(b, &letter_counts, normalized_weights, common_bias))
+                        .unwrap()
+                })
+                .unwrap_or_else(|| panic!("Empty wordlist"))
+                .clone()
+        }
+        Strategy::Entropy => {
+            let scored: Vec<(&String, f64)> = all_words
+                .iter()
+                .map(|word| {
+                    let score = blended_entropy(word, words, length, normalized_weights, common_bias, pattern_cache);
+                    (word, score)
+                })
+                .collect();
+
+            scored
+                .into_iter()
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap_or_else(|| panic!("Empty wordlist"))
+                .0
+                .clone()
+        }
+    }
+}
+
+fn blended_score(
+    word: &str,
+    letter_counts: &HashMap<char, i32>,
+    normalized_weights: &HashMap<String, f64>,
+    common_bias: f64,
+) -> f64 {
+    score(word, letter_counts) as f64 + common_bias * normalized_weights.get(word).copied().unwrap_or(0.0)
+}
+
+fn blended_entropy(
+    word: &str,
+    candidates: &HashSet<String>,
+    length: usize,
+    normalized_weights: &HashMap<String, f64>,
+    common_bias: f64,
+    pattern_cache: &mut PatternCache,
+) -> f64 {
+    entropy(word, candidates, length, pattern_cache) + common_bias * normalized_weights.get(word).copied().unwrap_or(0.0)
+}
+
+fn run_benchmark(
+    all_words: &[String],
+    strategy: Strategy,
+    length: usize,
+    max_guesses: usize,
+    common_bias: f64,
+    normalized_weights: &HashMap<String, f64>,
+) {
+    let mut guess_counts: Vec<usize> = Vec::with_capacity(all_words.len());
+    let mut worst: Vec<(String, usize)> = Vec::new();
+    let mut pattern_cache: PatternCache = HashMap::new();
+
+    let step_cap = max_guesses.max(1) * 4;
+
+    for solution in all_words {
+        let mut words: HashSet<String> = all_words.iter().cloned().collect();
+        let mut steps = 0;
+
+        loop {
+            if steps >= step_cap {
+                eprintln!(
+                    "Warning: `{}` did not converge within {} guesses (strategy={:?}, common_bias={}) -- recording as non-convergent",
+                    solution, step_cap, strategy, common_bias
+                );
+                break;
+            }
+            steps += 1;
+
+            let guess = recommend(
+                &words,
+                all_words,
+                strategy,
+                length,
+                common_bias,
+                normalized_weights,
+                &mut pattern_cache,
+            );
+            if guess == *solution || words.len() == 1 {
+                break;
+            }
+
+            let trits = feedback_trits(&guess, solution, length);
+            apply_feedback(&mut words, &guess, &trits);
+        }
+
+        guess_counts.push(steps);
+        worst.push((solution.clone(), steps));
     }
+
+    let mut distribution: HashMap<usize, usize> = HashMap::new();
+    for &steps in &guess_counts {
+        *distribution.entry(steps).or_insert(0) += 1;
+    }
+
+    let mean = guess_counts.iter().sum::<usize>() as f64 / guess_counts.len() as f64;
+    let wins = guess_counts.iter().filter(|&&steps| steps <= max_guesses).count();
+    let win_rate = wins as f64 / guess_counts.len() as f64;
+
+    let mut sorted_distribution: Vec<(usize, usize)> = distribution.into_iter().collect();
+    sorted_distribution.sort_by_key(|&(steps, _)| steps);
+
+    worst.sort_by_key(|&(_, steps)| std::cmp::Reverse(steps));
+    worst.truncate(10);
+
+    println!("Benchmark over {} words ({:?} strategy)", all_words.len(), strategy);
+    println!("Distribution (guesses -> count): {:?}", sorted_distribution);
+    println!("Mean guesses: {:.3}", mean);
+    println!("Win rate (<= {} guesses): {:.2}%", max_guesses, win_rate * 100.0);
+    println!("Worst-case words: {:?}", worst);
+}
+
+fn run_play(all_words: &[String], length: usize, max_guesses: usize) {
+    use rand::seq::SliceRandom;
+
+    let solution = all_words
+        .choose(&mut rand::thread_rng())
+        .unwrap_or_else(|| panic!("Empty wordlist"));
+
+    println!("Guess the {}-letter word in {} tries!", length, max_guesses);
+
+    let mut attempt = 0;
+    while attempt < max_guesses {
+        print!("Guess {}/{}: ", attempt + 1, max_guesses);
+        std::io::stdout().flush().unwrap();
+
+        let mut guess = String::new();
+        if std::io::stdin().read_line(&mut guess).unwrap() == 0 {
+            println!("No more input. The word was: {}", solution);
+            return;
+        }
+        let guess = guess.trim().to_ascii_lowercase();
+
+        if guess.chars().count() != length {
+            println!("Guess must be {} letters long", length);
+            continue;
+        }
+        attempt += 1;
+
+        let tiles = evaluate(solution, &guess, length);
+        println!("{}", render_tiles(&tiles));
+
+        if guess == *solution {
+            println!("You got it in {} guess(es)!", attempt);
+            return;
+        }
+    }
+
+    println!("Out of guesses. The word was: {}", solution);
+}
+
+fn render_tiles(tiles: &[Tile]) -> String {
+    tiles
+        .iter()
+        .map(|tile| match tile {
+            Tile::Correct(c) => format!("\x1b[30;42m {} \x1b[0m", c.to_ascii_uppercase()),
+            Tile::Incorrect(c) => format!("\x1b[30;43m {} \x1b[0m", c.to_ascii_uppercase()),
+            Tile::Unused(c) => format!("\x1b[97;100m {} \x1b[0m", c.to_ascii_uppercase()),
+            Tile::Unchecked(c) => format!(" {} ", c.to_ascii_uppercase()),
+        })
+        .collect()
 }
 
 fn prune(words: &mut HashSet<String>, ch: char) {
@@ -232,3 +586,105 @@ fn letter_counts(words: &HashSet<String>) -> HashMap<char, i32> {
 fn score(word: &str, letter_counts: &HashMap<char, i32>) -> i32 {
     word.chars().map(|c| letter_counts.get(&c).unwrap()).sum()
 }
+
+fn evaluate(solution: &str, guess: &str, length: usize) -> Vec<Tile> {
+    let guess: Vec<char> = guess.chars().collect();
+    let solution: Vec<char> = solution.chars().collect();
+    let mut remaining: HashMap<char, i32> = HashMap::new();
+    let mut tiles: Vec<Tile> = guess.iter().copied().map(Tile::Unchecked).collect();
+
+    for i in 0..length {
+        if guess[i] == solution[i] {
+            tiles[i] = Tile::Correct(guess[i]);
+        } else {
+            *remaining.entry(solution[i]).or_insert(0) += 1;
+        }
+    }
+
+    for tile in tiles.iter_mut() {
+        if let Tile::Unchecked(c) = *tile {
+            match remaining.get_mut(&c) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    *tile = Tile::Incorrect(c);
+                }
+                _ => *tile = Tile::Unused(c),
+            }
+        }
+    }
+
+    tiles
+}
+
+fn feedback_trits(guess: &str, solution: &str, length: usize) -> Vec<usize> {
+    evaluate(solution, guess, length)
+        .iter()
+        .map(|tile| match tile {
+            Tile::Correct(_) => 2,
+            Tile::Incorrect(_) => 1,
+            Tile::Unused(_) => 0,
+            Tile::Unchecked(_) => unreachable!(),
+        })
+        .collect()
+}
+
+fn feedback_pattern(guess: &str, solution: &str, length: usize, pattern_cache: &mut PatternCache) -> usize {
+    if let Some(&pattern) = pattern_cache.get(&(guess.to_string(), solution.to_string())) {
+        return pattern;
+    }
+
+    let pattern = feedback_trits(guess, solution, length)
+        .iter()
+        .enumerate()
+        .map(|(i, t)| t * 3usize.pow(i as u32))
+        .sum();
+
+    pattern_cache.insert((guess.to_string(), solution.to_string()), pattern);
+    pattern
+}
+
+fn apply_feedback(words: &mut HashSet<String>, guess: &str, trits: &[usize]) {
+    let chars: Vec<char> = guess.chars().collect();
+    let marked: Vec<char> = chars
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| trits[*i] != 0)
+        .map(|(_, c)| *c)
+        .collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        match trits[i] {
+            2 => require_at(words, c, i),
+            1 => {
+                prune_at(words, c, i);
+                require(words, c);
+            }
+            0 => {
+                if marked.contains(&c) {
+                    prune_at(words, c, i);
+                } else {
+                    prune(words, c);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn entropy(guess: &str, candidates: &HashSet<String>, length: usize, pattern_cache: &mut PatternCache) -> f64 {
+    let mut buckets: HashMap<usize, u32> = HashMap::new();
+    for solution in candidates {
+        *buckets
+            .entry(feedback_pattern(guess, solution, length, pattern_cache))
+            .or_insert(0) += 1;
+    }
+
+    let total = candidates.len() as f64;
+    buckets
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}